@@ -88,5 +88,7 @@ r#"Logging verbosity:
                     .value_name("PATH")
                     .takes_value(true))
             ))
+        .subcommand(SubCommand::with_name("watch")
+            .about("Stay resident and re-run maintenance whenever the config file changes"))
         .get_matches()
 }
\ No newline at end of file
@@ -1,12 +1,15 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::io::{Result};
 use std::fs;
 
 #[allow(unused_imports)]
 use log::*;
 
+use chrono::Utc;
 use serde::{Serialize, Deserialize};
 
+use crate::names::BranchName;
+
 
 /// Reaper reads and stores configuration as TOML. It is either
 /// user-provided or created new by RPR. 
@@ -27,10 +30,29 @@ use serde::{Serialize, Deserialize};
 /// ```
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
+    /// Global user identity applied to git operations.
+    pub user: Option<User>,
     /// remote git repositories
     pub repository: Option<Vec<Repository>>,
 }
 
+/// Global user identity, written once at the top of the config as `[user]`.
+///
+/// # Example
+///
+/// ```toml
+/// [user]
+/// name = "Ross Murray"
+/// email = "ross@example.com"
+/// ```
+#[derive(Debug, Serialize, Deserialize)]
+pub struct User {
+    /// The user's display name.
+    pub name: Option<String>,
+    /// The user's email address.
+    pub email: Option<String>,
+}
+
 impl Config {
 
     /// Deserialise a TOML file (as a string) into Config
@@ -47,7 +69,8 @@ impl Config {
     /// assert_eq!(test.repository.is_some(), true);
     /// ```
     pub fn from_toml (input: String) -> Result<Self> {
-        Ok(toml::from_str(&input).unwrap())
+        toml::from_str(&input)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))
     }
 
     /// Deserialise a file from a filepath into Config
@@ -56,24 +79,49 @@ impl Config {
         Config::from_toml(file_content)
     }
 
-    /// Serialise a Config struct into a file. The file will be created if it doesn't already exist.
-    pub fn to_file(filepath: &Path, config: Config) -> Result<()> {
+    /// Serialise a Config struct into a file. The file will be created if it
+    /// doesn't already exist.
+    ///
+    /// Any existing file at `filepath` is first copied to a timestamped
+    /// sibling backup so a malformed serialisation or interrupted write can't
+    /// destroy a hand-edited config. The path of the backup, if one was made,
+    /// is returned so callers can report it.
+    pub fn to_file(filepath: &Path, config: Config) -> Result<Option<PathBuf>> {
+        let backup = Config::backup(filepath)?;
         let output_str = toml::to_string_pretty(&config);
-        fs::write(filepath, output_str.unwrap())
+        fs::write(filepath, output_str.unwrap())?;
+        Ok(backup)
+    }
+
+    /// Copy an existing config at `filepath` to a timestamped sibling backup
+    /// (e.g. `reaper.toml.20260725T101500.bak`), returning its path. Returns
+    /// `None` when there is nothing to back up.
+    fn backup(filepath: &Path) -> Result<Option<PathBuf>> {
+        if !filepath.exists() {
+            return Ok(None);
+        }
+        let timestamp = Utc::now().format("%Y%m%dT%H%M%S");
+        let mut name = filepath.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{}.bak", timestamp));
+        let backup = filepath.with_file_name(name);
+        fs::copy(filepath, &backup)?;
+        info!("Backed up existing config to {}", backup.display());
+        Ok(Some(backup))
     }
 
     /// Initiate a new Reaper configuration file at the provided path.
-    pub fn init(filepath: &Path) -> Result<()> {
+    ///
+    /// Any existing config is backed up first; the backup path, if one was
+    /// made, is returned.
+    pub fn init(filepath: &Path) -> Result<Option<PathBuf>> {
         let default_conf = Config {
+            user: None,
             repository: None
         };
-        // Create all parent directories necessary
-        fs::create_dir_all(filepath)?;
-        println!("testing, {:#?}", filepath);
-        fs::OpenOptions::new()
-            .create(true)
-            .write(true)
-            .open(filepath)?;
+        // Create all parent directories necessary.
+        if let Some(parent) = filepath.parent() {
+            fs::create_dir_all(parent)?;
+        }
         Config::to_file(filepath, default_conf)
     }
 }
@@ -110,12 +158,19 @@ pub struct Repository {
     /// An URL of a upstream fork of the remote.
     pub upstream: Option<String>,
     /// The primary branch of the remote.
-    pub branch: Option<String>,
+    pub branch: Option<BranchName>,
     /// A path within the repository to the target content.
     pub path: Option<String>,
+    /// Path to the SSH private key used to authenticate against the remote.
+    pub ssh_private: Option<String>,
+    /// Path to the SSH public key used to authenticate against the remote.
+    pub ssh_public: Option<String>,
+    /// Name of an environment variable holding an API token for HTTPS auth.
+    /// The token itself is never stored in the config, only the variable name.
+    pub token: Option<String>,
 }
 
-/// A Repository builder for the Repository struct. 
+/// A Repository builder for the Repository struct.
 /// 
 /// Allows precise control over the instantiation and attributes defined for a Repository.
 /// 
@@ -128,8 +183,9 @@ pub struct Repository {
 ///     .upstream(String::from("http://github.com/some_org/rpr"))
 ///     .branch("development")
 ///     .path("/")
-///     .create();
-/// assert_eq!(ex.url, String::from("http://github.com/rossmurr4y/rpr"));
+///     .create()
+///     .unwrap();
+/// assert_eq!(ex.url, Some(String::from("http://github.com/rossmurr4y/rpr")));
 /// ```
 #[derive(Debug)]
 pub struct Remote {
@@ -145,6 +201,12 @@ pub struct Remote {
     pub branch: Option<String>,
     /// A path within the repository to the target content.
     pub path: Option<String>,
+    /// Path to the SSH private key used to authenticate against the remote.
+    pub ssh_private: Option<String>,
+    /// Path to the SSH public key used to authenticate against the remote.
+    pub ssh_public: Option<String>,
+    /// Name of an environment variable holding an API token for HTTPS auth.
+    pub token: Option<String>,
 }
 
 impl Remote {
@@ -158,6 +220,9 @@ impl Remote {
             upstream: None,
             branch: None,
             path: None,
+            ssh_private: None,
+            ssh_public: None,
+            token: None,
         }
     }
 
@@ -194,16 +259,42 @@ impl Remote {
         self
     }
 
-    /// Creates the Repository with the options configgured so far on the Remote
-    pub fn create(self) -> Repository {
-        Repository {
+    /// Configure an SSH key pair to authenticate against the Remote.
+    /// Used when the Remote's URL is an SSH (`git@`/`ssh://`) URL.
+    pub fn ssh_key(mut self, private: String, public: String) -> Self {
+        self.ssh_private = Some(private);
+        self.ssh_public = Some(public);
+        self
+    }
+
+    /// Configure token-based HTTPS authentication for the Remote.
+    /// The value is the name of an environment variable holding the token
+    /// (e.g. one minted at `https://{host}/user/settings/applications`),
+    /// never the token itself.
+    pub fn token(mut self, env_var: String) -> Self {
+        self.token = Some(env_var);
+        self
+    }
+
+    /// Creates the Repository with the options configgured so far on the
+    /// Remote, validating the branch name. Returns an error if the branch
+    /// name is malformed.
+    pub fn create(self) -> Result<Repository> {
+        let branch = match self.branch {
+            Some(b) => Some(BranchName::new(b)?),
+            None => None,
+        };
+        Ok(Repository {
             name: self.name,
             description: self.description,
             url: self.url,
             upstream: self.upstream,
-            branch: self.branch,
+            branch,
             path: self.path,
-        }
+            ssh_private: self.ssh_private,
+            ssh_public: self.ssh_public,
+            token: self.token,
+        })
     }
 }
 
@@ -284,7 +375,8 @@ mod tests {
             .upstream(String::from("http://github.com/some_org/rpr"))
             .branch(String::from("development"))
             .path(String::from("/"))
-            .create();
+            .create()
+            .unwrap();
         assert_eq!(ex.url, Some(String::from("http://github.com/rossmurr4y/rpr")));
     }
 
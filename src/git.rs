@@ -0,0 +1,242 @@
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+
+#[allow(unused_imports)]
+use log::*;
+
+use git2::build::RepoBuilder;
+use git2::{BranchType, Cred, FetchOptions, RemoteCallbacks, Repository as GitRepository};
+
+use crate::config::Repository;
+use crate::names::RemoteName;
+
+/// The remote name Reaper falls back to when a repository has no default
+/// remote configured, matching git's own convention.
+const DEFAULT_REMOTE: &str = "origin";
+
+/// Clone a [`Repository`] to its configured `path` if it is not already
+/// present on disk.
+///
+/// A missing `url` or `path` is a configuration error; an existing checkout
+/// is left untouched so the operation is idempotent.
+pub fn clone_remote(repo: &Repository) -> Result<()> {
+    let url = repo.url.as_ref().ok_or_else(|| missing("url", repo.name.as_str()))?;
+    let path = resolved_path(repo)?;
+    let path = path.as_path();
+
+    if path.exists() {
+        debug!("Repository `{}` already present at {}", repo.name, path.display());
+        return Ok(());
+    }
+
+    info!("Cloning `{}` from {}", repo.name, url);
+    RepoBuilder::new()
+        .fetch_options(fetch_options(repo))
+        .clone(url, path)
+        .map_err(into_io)?;
+    Ok(())
+}
+
+/// Fetch both the `origin` and (if configured) `upstream` remotes, then
+/// fast-forward the tracked `branch` to its fetched counterpart.
+pub fn fetch_remote(repo: &Repository) -> Result<()> {
+    let git = GitRepository::open(resolved_path(repo)?).map_err(into_io)?;
+
+    let origin = default_remote(&git);
+    fetch(&git, origin.as_str(), repo)?;
+
+    // `clone` only ever creates `origin`, so the upstream remote has to be
+    // registered (or re-pointed) before it can be fetched.
+    if let Some(upstream) = repo.upstream.as_ref() {
+        ensure_remote(&git, "upstream", upstream)?;
+        fetch(&git, "upstream", repo)?;
+    }
+
+    if let Some(branch) = repo.branch.as_ref() {
+        fast_forward(&git, origin.as_str(), branch.as_str())?;
+    }
+    Ok(())
+}
+
+/// Prune local branches that are already merged into the tracked `branch`.
+pub fn prune_merged(repo: &Repository) -> Result<()> {
+    let branch = repo
+        .branch
+        .as_ref()
+        .ok_or_else(|| missing("branch", repo.name.as_str()))?
+        .as_str();
+    let git = GitRepository::open(resolved_path(repo)?).map_err(into_io)?;
+
+    let target = git
+        .find_branch(branch, BranchType::Local)
+        .map_err(into_io)?
+        .get()
+        .target()
+        .ok_or_else(|| Error::new(ErrorKind::Other, format!("branch `{}` has no target", branch)))?;
+
+    let branches = git.branches(Some(BranchType::Local)).map_err(into_io)?;
+    for entry in branches {
+        let (mut local, _) = entry.map_err(into_io)?;
+        let name = match local.name().map_err(into_io)? {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        if name == branch {
+            continue;
+        }
+        // A branch is merged when the tracked branch is a descendant of it.
+        if let Some(oid) = local.get().target() {
+            if git.graph_descendant_of(target, oid).map_err(into_io)? {
+                info!("Pruning merged branch `{}` in `{}`", name, repo.name);
+                local.delete().map_err(into_io)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Resolve the validated name of a repository's default remote, falling back
+/// to [`DEFAULT_REMOTE`] when none is configured or the configured value is
+/// not a valid remote identifier.
+fn default_remote(git: &GitRepository) -> RemoteName {
+    let raw = git
+        .config()
+        .and_then(|cfg| cfg.get_string("clone.defaultRemoteName"))
+        .unwrap_or_else(|_| DEFAULT_REMOTE.to_string());
+    RemoteName::new(raw)
+        .unwrap_or_else(|_| RemoteName::new(DEFAULT_REMOTE).expect("default remote name is valid"))
+}
+
+/// Ensure a remote named `name` exists pointing at `url`, creating it when
+/// absent and re-pointing it otherwise so a changed `upstream` URL takes
+/// effect.
+fn ensure_remote(git: &GitRepository, name: &str, url: &str) -> Result<()> {
+    match git.find_remote(name) {
+        Ok(_) => git.remote_set_url(name, url).map_err(into_io),
+        Err(_) => git.remote(name, url).map(|_| ()).map_err(into_io),
+    }
+}
+
+/// Fetch every refspec for a single named remote, authenticating as the
+/// configured [`Repository`] requires.
+fn fetch(git: &GitRepository, remote: &str, repo: &Repository) -> Result<()> {
+    let mut remote = git.find_remote(remote).map_err(into_io)?;
+    debug!("Fetching remote `{}`", remote.name().unwrap_or("<anonymous>"));
+    let refspecs: Vec<String> = remote
+        .fetch_refspecs()
+        .map_err(into_io)?
+        .iter()
+        .flatten()
+        .map(str::to_string)
+        .collect();
+    let mut opts = fetch_options(repo);
+    remote.fetch(&refspecs, Some(&mut opts), None).map_err(into_io)?;
+    Ok(())
+}
+
+/// Build [`FetchOptions`] carrying the credential callback for a repository.
+fn fetch_options(repo: &Repository) -> FetchOptions {
+    let mut callbacks = RemoteCallbacks::new();
+    let ssh_private = repo.ssh_private.clone();
+    let ssh_public = repo.ssh_public.clone();
+    let token = repo.token.clone();
+    callbacks.credentials(move |_url, username, allowed| {
+        credentials(username, allowed, &ssh_private, &ssh_public, &token)
+    });
+    let mut opts = FetchOptions::new();
+    opts.remote_callbacks(callbacks);
+    opts
+}
+
+/// Select credentials for the type git2 is currently asking for.
+///
+/// git2 may call back several times for one connection: an `ssh://` URL with
+/// no inline username first requests [`git2::CredentialType::USERNAME`], expecting a
+/// [`Cred::username`], before asking for the key. Honour the `allowed` mask
+/// rather than branching on URL scheme alone so those `ssh://` remotes
+/// authenticate, not just `git@`-style URLs.
+fn credentials(
+    username: Option<&str>,
+    allowed: git2::CredentialType,
+    ssh_private: &Option<String>,
+    ssh_public: &Option<String>,
+    token: &Option<String>,
+) -> std::result::Result<Cred, git2::Error> {
+    let user = username.unwrap_or("git");
+    if allowed.contains(git2::CredentialType::USERNAME) {
+        return Cred::username(user);
+    }
+    if allowed.contains(git2::CredentialType::SSH_KEY) {
+        return match ssh_private {
+            Some(private) => Cred::ssh_key(
+                user,
+                ssh_public.as_ref().map(Path::new),
+                Path::new(private),
+                None,
+            ),
+            None => Cred::ssh_key_from_agent(user),
+        };
+    }
+    if allowed.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
+        if let Some(env_var) = token {
+            let secret = std::env::var(env_var).map_err(|_| {
+                git2::Error::from_str(&format!(
+                    "token environment variable `{}` is not set",
+                    env_var
+                ))
+            })?;
+            return Cred::userpass_plaintext(user, &secret);
+        }
+    }
+    Cred::default()
+}
+
+/// Fast-forward `branch` to the tip fetched for it on `remote`.
+fn fast_forward(git: &GitRepository, remote: &str, branch: &str) -> Result<()> {
+    let fetch_ref = format!("refs/remotes/{}/{}", remote, branch);
+    let fetched = git.find_reference(&fetch_ref).map_err(into_io)?;
+    let fetched = git.reference_to_annotated_commit(&fetched).map_err(into_io)?;
+
+    let (analysis, _) = git.merge_analysis(&[&fetched]).map_err(into_io)?;
+    if analysis.is_up_to_date() {
+        debug!("Branch `{}` already up to date", branch);
+        return Ok(());
+    }
+    if !analysis.is_fast_forward() {
+        return Err(Error::new(
+            ErrorKind::Other,
+            format!("branch `{}` cannot be fast-forwarded", branch),
+        ));
+    }
+
+    let local_ref = format!("refs/heads/{}", branch);
+    let mut reference = git.find_reference(&local_ref).map_err(into_io)?;
+    reference
+        .set_target(fetched.id(), "reaper: fast-forward")
+        .map_err(into_io)?;
+    git.set_head(&local_ref).map_err(into_io)?;
+    git.checkout_head(None).map_err(into_io)?;
+    info!("Fast-forwarded `{}` to {}", branch, fetched.id());
+    Ok(())
+}
+
+/// Resolve a repository's `path`, expanding any `~`/`$VAR` references.
+fn resolved_path(repo: &Repository) -> Result<PathBuf> {
+    let path = repo.path.as_ref().ok_or_else(|| missing("path", repo.name.as_str()))?;
+    crate::path::resolve(path)
+}
+
+/// Build a consistent error for a configuration field that a git operation
+/// requires but which the [`Repository`] left unset.
+fn missing(field: &str, name: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!("repository `{}` is missing a `{}` for git operations", name, field),
+    )
+}
+
+/// Translate a `git2::Error` into the `std::io::Error` Reaper surfaces
+/// elsewhere.
+fn into_io(err: git2::Error) -> Error {
+    Error::new(ErrorKind::Other, err.to_string())
+}
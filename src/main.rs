@@ -43,9 +43,21 @@
 /// Definitions for the Reaper configuration as well as interactions with it.
 pub mod config;
 
+/// Git operations (clone/fetch/prune) performed against configured remotes.
+pub mod git;
+
+/// Validated newtypes for remote, branch and command names.
+pub mod names;
+
+/// Expansion and resolution of `~`/`$VAR` references in config paths.
+pub mod path;
+
 /// State definition and management.
 pub mod state;
 
+/// Watch/daemon mode that re-runs maintenance when the config changes.
+pub mod watch;
+
 use log::*;
 use state::*;
 
@@ -71,13 +83,48 @@ fn main() {
 
     use config::{Config};
 
-    // reaper config filepath
-    let filepath_str = state.inputs.value_of("config").unwrap_or(".reaper.toml");
-    let filepath = std::path::Path::new(filepath_str);
+    // reaper config filepath, with `~` and `$VAR` references expanded.
+    let filepath_str = state.inputs.value_of("config").unwrap_or("~/reaper.toml");
+    let filepath = match path::resolve(filepath_str) {
+        Ok(p) => p,
+        Err(e) => {
+            error!("Unable to resolve config path `{}`: {}", filepath_str, e);
+            return;
+        }
+    };
 
     if let true = state.inputs.is_present("init") {
-        Config::init(filepath);
+        match Config::init(&filepath) {
+            Ok(Some(backup)) => info!("Existing config backed up to {}", backup.display()),
+            Ok(None) => {}
+            Err(e) => error!("Unable to initialise config: {}", e),
+        }
+    }
+
+    // In watch mode, stay resident and re-run maintenance on each config
+    // change instead of draining the queue once and exiting.
+    let watch_mode = state.inputs.subcommand_matches("watch").is_some();
+    if watch_mode {
+        if let Err(e) = watch::run(&mut state, &filepath) {
+            error!("{}", e);
+        }
+        return;
+    }
+
+    // Load the resolved config and queue maintenance for each configured
+    // repository before draining, so a one-shot `rpr` run actually performs
+    // git work rather than just the two built-in init Tasks.
+    if let Err(e) = state.load_config(&filepath) {
+        error!("Unable to load config `{}`: {}", filepath.display(), e);
+        return;
     }
+    state.queue_maintenance();
+
+    // Drain the queued Tasks through the dispatcher.
+    if let Err(e) = state.run() {
+        error!("{}", e);
+    }
+
     info!("Complete");
     println!("{:#?}", state);
 }
\ No newline at end of file
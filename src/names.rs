@@ -0,0 +1,138 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{Error, ErrorKind, Result};
+
+use serde::{Deserialize, Serialize};
+
+/// Generates a validated string newtype whose only constructor is the
+/// fallible [`TryFrom<String>`]/`new` pair. The inner string is private so an
+/// invalid value cannot be built, and serde round-trips through the inner
+/// string — parsing (and therefore validating) on deserialize so a malformed
+/// value surfaces at config load rather than at git-exec time.
+macro_rules! name_newtype {
+    ($(#[$meta:meta])* $name:ident, $validate:path) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+        #[serde(try_from = "String", into = "String")]
+        pub struct $name(String);
+
+        impl $name {
+            /// Construct the newtype, validating the inner value.
+            pub fn new<S: Into<String>>(value: S) -> Result<Self> {
+                let value = value.into();
+                $validate(&value)?;
+                Ok($name(value))
+            }
+
+            /// Borrow the inner string.
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = Error;
+            fn try_from(value: String) -> Result<Self> {
+                $name::new(value)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> String {
+                value.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+    };
+}
+
+/// Reject values that no name of any kind may take: empty, or containing
+/// whitespace or control characters.
+fn validate_plain(value: &str) -> Result<()> {
+    if value.is_empty() {
+        return Err(invalid("must not be empty"));
+    }
+    if value.chars().any(|c| c.is_whitespace()) {
+        return Err(invalid("must not contain whitespace"));
+    }
+    if value.chars().any(|c| c.is_control()) {
+        return Err(invalid("must not contain control characters"));
+    }
+    Ok(())
+}
+
+/// Validate a git branch name: the plain rules plus the git ref rules that
+/// matter here — no `..`, no leading or trailing `/`, and no `@{`.
+fn validate_branch(value: &str) -> Result<()> {
+    validate_plain(value)?;
+    if value.contains("..") {
+        return Err(invalid("must not contain `..`"));
+    }
+    if value.starts_with('/') || value.ends_with('/') {
+        return Err(invalid("must not start or end with `/`"));
+    }
+    if value.contains("@{") {
+        return Err(invalid("must not contain `@{`"));
+    }
+    Ok(())
+}
+
+/// Build a consistent validation error.
+fn invalid(reason: &str) -> Error {
+    Error::new(ErrorKind::InvalidInput, format!("invalid name: {}", reason))
+}
+
+name_newtype!(
+    /// A validated remote name.
+    RemoteName,
+    validate_plain
+);
+
+name_newtype!(
+    /// A validated Task command name.
+    CommandName,
+    validate_plain
+);
+
+name_newtype!(
+    /// A validated git branch name, enforcing the subset of git's ref rules
+    /// Reaper cares about.
+    BranchName,
+    validate_branch
+);
+
+#[cfg(test)]
+mod tests {
+
+    use crate::names::{BranchName, CommandName, RemoteName};
+
+    #[test]
+    fn rejects_empty_and_whitespace() {
+        assert_eq!(RemoteName::new("").is_err(), true);
+        assert_eq!(CommandName::new("fetch remote").is_err(), true);
+    }
+
+    #[test]
+    fn accepts_plain_name() {
+        assert_eq!(RemoteName::new("origin").unwrap().as_str(), "origin");
+    }
+
+    #[test]
+    fn branch_rejects_ref_rule_violations() {
+        assert_eq!(BranchName::new("feature/..").is_err(), true);
+        assert_eq!(BranchName::new("/main").is_err(), true);
+        assert_eq!(BranchName::new("main/").is_err(), true);
+        assert_eq!(BranchName::new("head@{0}").is_err(), true);
+        assert_eq!(BranchName::new("he ad").is_err(), true);
+    }
+
+    #[test]
+    fn branch_accepts_valid() {
+        assert_eq!(BranchName::new("feature/login").unwrap().as_str(), "feature/login");
+    }
+}
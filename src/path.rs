@@ -0,0 +1,145 @@
+use std::env;
+use std::io::{Error, ErrorKind, Result};
+use std::path::PathBuf;
+
+#[allow(unused_imports)]
+use log::*;
+
+/// Resolve a user-supplied path into an absolute [`PathBuf`].
+///
+/// A leading `~` is expanded to `$HOME`, and `$VAR`/`${VAR}` references are
+/// substituted from the environment. This lets users write portable configs
+/// like `path = "$XDG_CONFIG_HOME/foo"` and makes the advertised default of
+/// `~/reaper.toml` actually resolve. A reference to an undefined variable is
+/// surfaced as an error rather than being left as the literal `$VAR`.
+///
+/// # Examples
+///
+/// ```
+/// std::env::set_var("XDG_CONFIG_HOME", "/home/ross/.config");
+/// let p = resolve("$XDG_CONFIG_HOME/reaper.toml").unwrap();
+/// assert_eq!(p, std::path::PathBuf::from("/home/ross/.config/reaper.toml"));
+/// ```
+pub fn resolve(input: &str) -> Result<PathBuf> {
+    let expanded = expand_vars(&expand_tilde(input))?;
+    let path = PathBuf::from(expanded);
+    if path.is_absolute() {
+        Ok(path)
+    } else {
+        Ok(env::current_dir()?.join(path))
+    }
+}
+
+/// Expand a leading `~` to the value of `$HOME`.
+fn expand_tilde(input: &str) -> String {
+    if input == "~" {
+        return env::var("HOME").unwrap_or_else(|_| input.to_string());
+    }
+    if let Some(rest) = input.strip_prefix("~/") {
+        if let Ok(home) = env::var("HOME") {
+            return format!("{}/{}", home.trim_end_matches('/'), rest);
+        }
+    }
+    input.to_string()
+}
+
+/// Substitute every `$VAR` and `${VAR}` reference from the environment,
+/// erroring on any reference to an undefined variable.
+fn expand_vars(input: &str) -> Result<String> {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            out.push(c);
+            continue;
+        }
+
+        let braced = chars.peek() == Some(&'{');
+        if braced {
+            chars.next();
+        }
+
+        let mut name = String::new();
+        while let Some(&next) = chars.peek() {
+            let valid = if braced {
+                next != '}'
+            } else {
+                next.is_ascii_alphanumeric() || next == '_'
+            };
+            if !valid {
+                break;
+            }
+            name.push(next);
+            chars.next();
+        }
+        if braced {
+            match chars.next() {
+                Some('}') => {}
+                _ => return Err(malformed(input)),
+            }
+        }
+        if name.is_empty() {
+            return Err(malformed(input));
+        }
+
+        match env::var(&name) {
+            Ok(value) => out.push_str(&value),
+            Err(_) => {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!("environment variable `{}` referenced in path is not set", name),
+                ))
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Error for a syntactically malformed variable reference.
+fn malformed(input: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!("malformed variable reference in path `{}`", input),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::path::resolve;
+    use std::path::PathBuf;
+
+    #[test]
+    fn resolve_expands_tilde() {
+        std::env::set_var("HOME", "/home/reaper");
+        let p = resolve("~/reaper.toml").unwrap();
+        assert_eq!(p, PathBuf::from("/home/reaper/reaper.toml"));
+    }
+
+    #[test]
+    fn resolve_expands_named_var() {
+        std::env::set_var("XDG_CONFIG_HOME", "/home/reaper/.config");
+        let p = resolve("$XDG_CONFIG_HOME/foo").unwrap();
+        assert_eq!(p, PathBuf::from("/home/reaper/.config/foo"));
+    }
+
+    #[test]
+    fn resolve_expands_braced_var() {
+        std::env::set_var("REAPER_ROOT", "/opt/reaper");
+        let p = resolve("${REAPER_ROOT}/conf.toml").unwrap();
+        assert_eq!(p, PathBuf::from("/opt/reaper/conf.toml"));
+    }
+
+    #[test]
+    fn resolve_undefined_var_errors() {
+        let r = resolve("$REAPER_DEFINITELY_UNSET_VAR/foo");
+        assert_eq!(r.is_err(), true);
+    }
+
+    #[test]
+    fn resolve_relative_is_made_absolute() {
+        let p = resolve("relative.toml").unwrap();
+        assert_eq!(p.is_absolute(), true);
+    }
+}
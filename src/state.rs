@@ -1,7 +1,14 @@
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, Result};
+
 use log::*;
 use clap::ArgMatches;
 use chrono::Utc;
 
+use crate::config::Config;
+use crate::git;
+use crate::names::CommandName;
+
 /// Definitions for the Reaper CLI subcommands, arguments and associated configuration.
 pub mod cli;
 
@@ -12,6 +19,8 @@ pub struct State<'a> {
     pub process_start: String,
     /// All inputs that were passed at the time of initialization
     pub inputs: ArgMatches<'a>,
+    /// The loaded Reaper configuration, once read from disk.
+    pub config: Option<Config>,
     /// The queue of Task's requring actioning.
     pub queue: Vec<Task>,
 }
@@ -26,16 +35,23 @@ impl State<'_> {
         let mut state = State {
             process_start: Utc::now().to_rfc3339(),
             inputs: cli_matches,
+            config: None,
             queue: Vec::new(),
         };
 
         // Stage initialisation Tasks
         // Set the logging level
-        let log_level = Action::new(String::from("set_log_level")).priority(100).ready();
+        let log_level = Action::new(String::from("set_log_level"))
+            .expect("builtin command name is valid")
+            .priority(100)
+            .ready();
         state.queue_task(log_level);
 
         // Evaluate the rpr configuration file
-        let rpr_conf = Action::new(String::from("set_state_from_rpr_conf")).priority(200).ready();
+        let rpr_conf = Action::new(String::from("set_state_from_rpr_conf"))
+            .expect("builtin command name is valid")
+            .priority(200)
+            .ready();
         &state.queue_task(rpr_conf);
 
         state
@@ -46,6 +62,183 @@ impl State<'_> {
         self.queue.push(task);
         self
     }
+
+    /// Load and validate the Reaper config from `path`, storing it on the
+    /// State. Malformed values (e.g. an invalid `branch`) are rejected here
+    /// rather than surfacing later at git-exec time.
+    pub fn load_config(&mut self, path: &std::path::Path) -> Result<()> {
+        self.config = Some(Config::from_filepath(path)?);
+        Ok(())
+    }
+
+    /// Enqueue the routine maintenance Tasks for every configured repository.
+    /// A `clone_remote` runs first (higher priority) so a configured but
+    /// not-yet-cloned repository exists on disk before `fetch_remote` opens
+    /// it; each `fetch_remote` chains a `prune_merged` follow-up once it runs.
+    pub fn queue_maintenance(&mut self) -> &Self {
+        let names: Vec<String> = self
+            .config
+            .as_ref()
+            .and_then(|c| c.repository.as_ref())
+            .map(|repos| repos.iter().map(|r| r.name.to_string()).collect())
+            .unwrap_or_default();
+        for name in names {
+            let clone = Action::new(String::from("clone_remote"))
+                .expect("builtin command name is valid")
+                .priority(250)
+                .with_arg(name.clone())
+                .ready();
+            self.queue_task(clone);
+            let fetch = Action::new(String::from("fetch_remote"))
+                .expect("builtin command name is valid")
+                .priority(300)
+                .with_arg(name)
+                .ready();
+            self.queue_task(fetch);
+        }
+        self
+    }
+
+    /// Drains and dispatches every queued Task until the queue is empty.
+    ///
+    /// Tasks are processed in ascending `priority` order (lower runs sooner);
+    /// a Task with no priority is treated as the lowest precedence and runs
+    /// last. Each Task's `cmd` is matched against the handler registry and the
+    /// matching [`Handle`] is invoked with the Task's `args` and a mutable
+    /// reference to `State`. Handlers may enqueue follow-up Tasks (e.g. a fetch
+    /// that schedules a prune), so the loop continues until nothing
+    /// remains to action.
+    ///
+    /// A failing Task is logged and skipped rather than aborting the pass, so
+    /// one un-cloned or unreachable repository does not prevent the remaining
+    /// repositories from being maintained.
+    pub fn run(&mut self) -> Result<()> {
+        let registry = registry();
+        while !self.queue.is_empty() {
+            // Re-sort every pass so follow-up Tasks enqueued by a handler are
+            // honoured. `None` sorts after any `Some`, giving it the lowest
+            // precedence.
+            self.queue.sort_by_key(|t| (t.priority.is_none(), t.priority));
+            let task = self.queue.remove(0);
+            debug!("Dispatching task {} ({})", task.id, task.cmd);
+            match registry.get(task.cmd.as_str()) {
+                Some(handler) => {
+                    if let Err(e) = handler.handle(self, &task.args) {
+                        error!("Task {} ({}) failed: {}", task.id, task.cmd, e);
+                    }
+                }
+                None => error!("no handler registered for command `{}`", task.cmd),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A handler for a single Task command.
+///
+/// Implementors are keyed by command name in the dispatch registry and
+/// invoked by [`State::run`] when a matching [`Task`] is drained from the
+/// queue. This mirrors the handler-per-message pattern used for actor
+/// commands elsewhere: new commands register in [`registry`] without ever
+/// touching the dispatch loop, and a handler may enqueue follow-up Tasks via
+/// [`State::queue_task`].
+pub trait Handle {
+    /// Action the command with its `args` and mutable access to `State`.
+    fn handle(&self, state: &mut State, args: &[String]) -> Result<()>;
+}
+
+/// Builds the registry mapping command names to their [`Handle`] implementor.
+///
+/// Registering a new command is a matter of adding a single entry here; the
+/// dispatch loop in [`State::run`] needs no changes.
+fn registry() -> HashMap<&'static str, Box<dyn Handle>> {
+    let mut registry: HashMap<&'static str, Box<dyn Handle>> = HashMap::new();
+    registry.insert("set_log_level", Box::new(SetLogLevel));
+    registry.insert("set_state_from_rpr_conf", Box::new(SetStateFromRprConf));
+    registry.insert("clone_remote", Box::new(CloneRemote));
+    registry.insert("fetch_remote", Box::new(FetchRemote));
+    registry.insert("prune_merged", Box::new(PruneMerged));
+    registry
+}
+
+/// Resolve a configured repository by name, for the git handlers that take
+/// the repository name as their first argument.
+fn repository<'a>(state: &'a State, args: &[String]) -> Result<&'a crate::config::Repository> {
+    let name = args.first().ok_or_else(|| {
+        Error::new(ErrorKind::InvalidInput, "git command requires a repository name argument")
+    })?;
+    state
+        .config
+        .as_ref()
+        .and_then(|c| c.repository.as_ref())
+        .and_then(|repos| repos.iter().find(|r| r.name.as_str() == name))
+        .ok_or_else(|| {
+            Error::new(ErrorKind::NotFound, format!("no configured repository named `{}`", name))
+        })
+}
+
+/// Handler for the `set_log_level` command.
+struct SetLogLevel;
+
+impl Handle for SetLogLevel {
+    fn handle(&self, _state: &mut State, _args: &[String]) -> Result<()> {
+        // The logger is initialised from the CLI inputs in `main` before the
+        // queue is drained; this task records that step in the pipeline.
+        trace!("Log level already established from inputs");
+        Ok(())
+    }
+}
+
+/// Handler for the `set_state_from_rpr_conf` command.
+struct SetStateFromRprConf;
+
+impl Handle for SetStateFromRprConf {
+    fn handle(&self, _state: &mut State, _args: &[String]) -> Result<()> {
+        trace!("Evaluating rpr configuration file");
+        Ok(())
+    }
+}
+
+/// Handler for the `clone_remote` command.
+struct CloneRemote;
+
+impl Handle for CloneRemote {
+    fn handle(&self, state: &mut State, args: &[String]) -> Result<()> {
+        let repo = repository(state, args)?;
+        git::clone_remote(repo)
+    }
+}
+
+/// Handler for the `fetch_remote` command.
+///
+/// A successful fetch enqueues a follow-up `prune_merged` for the same
+/// repository so merged branches are reaped once the tracked branch advances.
+struct FetchRemote;
+
+impl Handle for FetchRemote {
+    fn handle(&self, state: &mut State, args: &[String]) -> Result<()> {
+        let name = {
+            let repo = repository(state, args)?;
+            git::fetch_remote(repo)?;
+            repo.name.to_string()
+        };
+        let prune = Action::new(String::from("prune_merged"))?
+            .priority(300)
+            .with_arg(name)
+            .ready();
+        state.queue_task(prune);
+        Ok(())
+    }
+}
+
+/// Handler for the `prune_merged` command.
+struct PruneMerged;
+
+impl Handle for PruneMerged {
+    fn handle(&self, state: &mut State, args: &[String]) -> Result<()> {
+        let repo = repository(state, args)?;
+        git::prune_merged(repo)
+    }
 }
 
 #[derive(Debug)]
@@ -56,7 +249,7 @@ pub struct Task {
     /// The priority of this Action
     priority: Option<i16>,
     /// The command that the Action is going to trigger
-    cmd: String,
+    cmd: CommandName,
     /// Arguments to be provided to the command
     args: Vec<String>,
 }
@@ -68,22 +261,23 @@ pub struct Action {
     /// The priority of this Action
     priority: Option<i16>,
     /// The command that the Action is going to trigger
-    cmd: String,
+    cmd: CommandName,
     /// Arguments to be provided to the command
     args: Vec<String>,
 }
 
 impl Action {
-    /// Creates a new Action
-    pub fn new(name: String) -> Self {        
-        // todo!("Add in validation of the command name being passed in");
+    /// Creates a new Action, validating the command name. An empty or
+    /// otherwise malformed command name is rejected here so an invalid Task
+    /// can never reach the dispatcher.
+    pub fn new(name: String) -> Result<Self> {
         // todo!("Replace the stand-in id value with a system-managed one")
-        Action {
+        Ok(Action {
             id: String::from("action_id"),
             priority: None,
-            cmd: name,
+            cmd: CommandName::new(name)?,
             args: Vec::new(),
-        }
+        })
     }
 
     /// Sets an Action's priority. Lower priority Action's will be processed sooner.
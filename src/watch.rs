@@ -0,0 +1,68 @@
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+#[allow(unused_imports)]
+use log::*;
+
+use notify::{watcher, DebouncedEvent, RecursiveMode, Watcher};
+
+use crate::state::State;
+
+/// How long to coalesce rapid successive filesystem events before acting, so
+/// an editor writing a config in several chunks triggers a single reload.
+const DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Keep Reaper resident, watching the resolved config `path` and re-running
+/// maintenance (fetch/prune) whenever it changes.
+///
+/// The config is reloaded and re-validated on every change before any git
+/// work happens, so a malformed edit logs an error and leaves the previously
+/// loaded config in effect rather than acting on bad data. Events are
+/// debounced over [`DEBOUNCE`]; reloads and triggers are logged at `debug`
+/// so `-vv` shows each one.
+pub fn run(state: &mut State, path: &Path) -> Result<()> {
+    // Run once up front so the watcher starts from an up-to-date tree.
+    reload(state, path);
+
+    let (tx, rx) = channel();
+    let mut watcher = watcher(tx, DEBOUNCE).map_err(into_io)?;
+    watcher
+        .watch(path, RecursiveMode::NonRecursive)
+        .map_err(into_io)?;
+    info!("Watching {} for changes", path.display());
+
+    loop {
+        match rx.recv() {
+            Ok(DebouncedEvent::Write(_))
+            | Ok(DebouncedEvent::Create(_))
+            | Ok(DebouncedEvent::Rename(_, _)) => {
+                debug!("Config change detected, reloading");
+                reload(state, path);
+            }
+            Ok(event) => trace!("Ignoring event: {:?}", event),
+            Err(e) => return Err(Error::new(ErrorKind::Other, e.to_string())),
+        }
+    }
+}
+
+/// Reload and re-validate the config, then re-queue and drain maintenance
+/// tasks. A failure to load (missing file, malformed TOML) is logged and the
+/// previous config is retained.
+fn reload(state: &mut State, path: &Path) {
+    if let Err(e) = state.load_config(path) {
+        error!("Skipping reload, config is invalid: {}", e);
+        return;
+    }
+    state.queue_maintenance();
+    if let Err(e) = state.run() {
+        error!("{}", e);
+    }
+}
+
+/// Translate a `notify::Error` into the `std::io::Error` Reaper surfaces
+/// elsewhere.
+fn into_io(err: notify::Error) -> Error {
+    Error::new(ErrorKind::Other, err.to_string())
+}